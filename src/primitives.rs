@@ -1,13 +1,32 @@
+extern crate chrono;
+extern crate futures;
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
 use std::sync::{Arc, RwLock};
-use subject::{self, Source, Mapper, WrapListener, Subject, Receiver};
+use subject::{self, Source, Mapper, WrapListener, Subject, Receiver, Listener};
 
 
 pub trait Event<A> {
     fn map<B, F>(&self, f: F) -> Map<A, B, F>
-        where F: Fn(A) -> B;
+        where B: Send + Sync + Clone,
+              F: Fn(A) -> B + Send + Sync;
     fn filter<F>(&self, f: F) -> Filter<A, F>
         where F: Fn(&A) -> bool + Send + Sync;
     fn iter(&self) -> Iter<A>;
+    fn hold(&self, initial: A) -> Signal<A>;
+    fn fold<B, F>(&self, init: B, f: F) -> Signal<B>
+        where B: Send + Sync + Clone,
+              F: Fn(&B, A) -> B + Send + Sync;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+pub trait Listen<A>: sealed::Sealed {
+    fn listen<L: Listener<A> + Send + Sync + 'static>(&self, listener: L);
 }
 
 
@@ -44,6 +63,25 @@ impl<A: Send + Sync + Clone> Event<A> for Sink<A> {
     fn iter(&self) -> Iter<A> {
         Iter::new(&mut *self.source.write().unwrap())
     }
+
+    fn hold(&self, initial: A) -> Signal<A> {
+        Signal::new(&mut *self.source.write().unwrap(), initial)
+    }
+
+    fn fold<B, F>(&self, init: B, f: F) -> Signal<B>
+        where B: Send + Sync + Clone,
+              F: Fn(&B, A) -> B + Send + Sync,
+    {
+        Signal::fold(&mut *self.source.write().unwrap(), init, f)
+    }
+}
+
+impl<A> sealed::Sealed for Sink<A> {}
+
+impl<A: Send + Sync + Clone> Listen<A> for Sink<A> {
+    fn listen<L: Listener<A> + Send + Sync + 'static>(&self, listener: L) {
+        self.source.write().unwrap().listen(listener);
+    }
 }
 
 
@@ -84,6 +122,29 @@ impl<A, B, F> Event<B> for Map<A, B, F>
     fn iter(&self) -> Iter<B> {
         Iter::new(&mut *self.mapper.write().unwrap())
     }
+
+    fn hold(&self, initial: B) -> Signal<B> {
+        Signal::new(&mut *self.mapper.write().unwrap(), initial)
+    }
+
+    fn fold<C, G>(&self, init: C, g: G) -> Signal<C>
+        where C: Send + Sync + Clone,
+              G: Fn(&C, B) -> C + Send + Sync,
+    {
+        Signal::fold(&mut *self.mapper.write().unwrap(), init, g)
+    }
+}
+
+impl<A, B, F> sealed::Sealed for Map<A, B, F> {}
+
+impl<A, B, F> Listen<B> for Map<A, B, F>
+    where A: Send + Sync + Clone,
+          B: Send + Sync + Clone,
+          F: Fn(A) -> B + Send + Sync,
+{
+    fn listen<L: Listener<B> + Send + Sync + 'static>(&self, listener: L) {
+        self.mapper.write().unwrap().listen(listener);
+    }
 }
 
 
@@ -124,6 +185,28 @@ impl<A, F> Event<A> for Filter<A, F>
     fn iter(&self) -> Iter<A> {
         Iter::new(&mut *self.filter.write().unwrap())
     }
+
+    fn hold(&self, initial: A) -> Signal<A> {
+        Signal::new(&mut *self.filter.write().unwrap(), initial)
+    }
+
+    fn fold<B, G>(&self, init: B, g: G) -> Signal<B>
+        where B: Send + Sync + Clone,
+              G: Fn(&B, A) -> B + Send + Sync,
+    {
+        Signal::fold(&mut *self.filter.write().unwrap(), init, g)
+    }
+}
+
+impl<A, F> sealed::Sealed for Filter<A, F> {}
+
+impl<A, F> Listen<A> for Filter<A, F>
+    where A: Send + Sync + Clone,
+          F: Fn(&A) -> bool + Send + Sync,
+{
+    fn listen<L: Listener<A> + Send + Sync + 'static>(&self, listener: L) {
+        self.filter.write().unwrap().listen(listener);
+    }
 }
 
 
@@ -146,6 +229,538 @@ impl<A: Send + Sync> Iterator for Iter<A> {
     }
 }
 
+impl<A: Send + Sync> Iter<A> {
+    pub fn into_stream(self) -> EventStream<A> {
+        EventStream { recv: self.recv }
+    }
+}
+
+pub struct EventStream<A> {
+    recv: Arc<RwLock<Receiver<A>>>,
+}
+
+impl<A: Send + Sync> futures::Stream for EventStream<A> {
+    type Item = A;
+    type Error = ();
+
+    fn poll(&mut self) -> futures::Poll<Option<A>, ()> {
+        let mut recv = self.recv.write().unwrap();
+        match recv.try_next() {
+            Some(a) => Ok(futures::Async::Ready(Some(a))),
+            None => {
+                recv.park(futures::task::current());
+                Ok(futures::Async::NotReady)
+            }
+        }
+    }
+}
+
+
+#[derive(Clone)]
+pub enum Change<A> {
+    Changed(A),
+    Unchanged(A),
+}
+
+impl<A: Clone> Change<A> {
+    fn value(&self) -> A {
+        match *self {
+            Change::Changed(ref a) | Change::Unchanged(ref a) => a.clone(),
+        }
+    }
+}
+
+
+struct Holder<A> {
+    current: Change<A>,
+    listeners: Vec<Box<Listener<A>>>,
+}
+
+impl<A: Send + Sync + Clone> Holder<A> {
+    fn new(initial: A) -> Holder<A> {
+        Holder { current: Change::Unchanged(initial), listeners: Vec::new() }
+    }
+}
+
+impl<A: Send + Sync + Clone> Listener<A> for Holder<A> {
+    fn listen(&mut self, a: A) {
+        self.current = Change::Changed(a.clone());
+        for listener in &mut self.listeners {
+            listener.listen(a.clone());
+        }
+    }
+}
+
+impl<A: Send + Sync + Clone> Subject<A> for Holder<A> {
+    fn listen<L: Listener<A> + Send + Sync + 'static>(&mut self, listener: L) {
+        self.listeners.push(Box::new(listener));
+    }
+}
+
+
+pub struct Signal<A> {
+    holder: Arc<RwLock<Holder<A>>>,
+}
+
+impl<A: Send + Sync + Clone> Signal<A> {
+    fn new<S: Subject<A>>(sub: &mut S, initial: A) -> Signal<A> {
+        let signal = Signal { holder: Arc::new(RwLock::new(Holder::new(initial))) };
+        sub.listen(signal.holder.wrap());
+        signal
+    }
+
+    pub fn sample(&self) -> A {
+        let mut holder = self.holder.write().unwrap();
+        let value = holder.current.value();
+        holder.current = Change::Unchanged(value.clone());
+        value
+    }
+
+    pub fn snapshot<B>(&self, event: &impl Event<B>) -> impl Event<(A, B)>
+        where A: 'static,
+              B: Send + Sync + Clone,
+    {
+        let holder = self.holder.clone();
+        event.map(move |b| (holder.read().unwrap().current.value(), b))
+    }
+
+    // Eager: recomputes f on every upstream push, not on sample.
+    pub fn map<B, F>(&self, f: F) -> Signal<B>
+        where B: Send + Sync + Clone,
+              F: Fn(A) -> B + Send + Sync,
+    {
+        let initial = f(self.sample());
+        Map::new(&mut *self.holder.write().unwrap(), f).hold(initial)
+    }
+
+    fn fold<S: Subject<A>, B, F>(sub: &mut S, init: B, f: F) -> Signal<B>
+        where B: Send + Sync + Clone,
+              F: Fn(&B, A) -> B + Send + Sync,
+    {
+        let node = Arc::new(RwLock::new(Fold {
+            current: init.clone(),
+            f: f,
+            listeners: Vec::new(),
+            _incoming: PhantomData,
+        }));
+        sub.listen(node.wrap());
+        Signal::new(&mut *node.write().unwrap(), init)
+    }
+}
+
+impl<A: Send + Sync + Clone> Event<A> for Signal<A> {
+    fn map<B, F>(&self, f: F) -> Map<A, B, F>
+        where B: Send + Sync + Clone,
+              F: Fn(A) -> B + Send + Sync,
+    {
+        Map::new(&mut *self.holder.write().unwrap(), f)
+    }
+
+    fn filter<F>(&self, f: F) -> Filter<A, F>
+        where F: Fn(&A) -> bool + Send + Sync,
+    {
+        Filter::new(&mut *self.holder.write().unwrap(), f)
+    }
+
+    fn iter(&self) -> Iter<A> {
+        Iter::new(&mut *self.holder.write().unwrap())
+    }
+
+    fn hold(&self, initial: A) -> Signal<A> {
+        Signal::new(&mut *self.holder.write().unwrap(), initial)
+    }
+
+    fn fold<B, F>(&self, init: B, f: F) -> Signal<B>
+        where B: Send + Sync + Clone,
+              F: Fn(&B, A) -> B + Send + Sync,
+    {
+        Signal::fold(&mut *self.holder.write().unwrap(), init, f)
+    }
+}
+
+impl<A> sealed::Sealed for Signal<A> {}
+
+impl<A: Send + Sync + Clone> Listen<A> for Signal<A> {
+    fn listen<L: Listener<A> + Send + Sync + 'static>(&self, listener: L) {
+        self.holder.write().unwrap().listen(listener);
+    }
+}
+
+
+struct Fold<A, B, F> {
+    current: B,
+    f: F,
+    listeners: Vec<Box<Listener<B>>>,
+    _incoming: PhantomData<A>,
+}
+
+impl<A, B, F> Listener<A> for Fold<A, B, F>
+    where A: Send + Sync + Clone,
+          B: Send + Sync + Clone,
+          F: Fn(&B, A) -> B + Send + Sync,
+{
+    fn listen(&mut self, a: A) {
+        self.current = (self.f)(&self.current, a);
+        let current = self.current.clone();
+        for listener in &mut self.listeners {
+            listener.listen(current.clone());
+        }
+    }
+}
+
+impl<A, B, F> Subject<B> for Fold<A, B, F>
+    where A: Send + Sync + Clone,
+          B: Send + Sync + Clone,
+          F: Fn(&B, A) -> B + Send + Sync,
+{
+    fn listen<L: Listener<B> + Send + Sync + 'static>(&mut self, listener: L) {
+        self.listeners.push(Box::new(listener));
+    }
+}
+
+
+struct Lift2<A, B, C, F> {
+    a: A,
+    b: B,
+    f: F,
+    listeners: Vec<Box<Listener<C>>>,
+}
+
+impl<A, B, C, F> Lift2<A, B, C, F>
+    where A: Send + Sync + Clone,
+          B: Send + Sync + Clone,
+          C: Send + Sync + Clone,
+          F: Fn(A, B) -> C + Send + Sync,
+{
+    fn recompute(&mut self) {
+        let c = (self.f)(self.a.clone(), self.b.clone());
+        for listener in &mut self.listeners {
+            listener.listen(c.clone());
+        }
+    }
+}
+
+impl<A, B, C, F> Subject<C> for Lift2<A, B, C, F>
+    where A: Send + Sync + Clone,
+          B: Send + Sync + Clone,
+          C: Send + Sync + Clone,
+          F: Fn(A, B) -> C + Send + Sync,
+{
+    fn listen<L: Listener<C> + Send + Sync + 'static>(&mut self, listener: L) {
+        self.listeners.push(Box::new(listener));
+    }
+}
+
+struct Lift2InputA<A, B, C, F> {
+    node: Arc<RwLock<Lift2<A, B, C, F>>>,
+}
+
+impl<A, B, C, F> Listener<A> for Lift2InputA<A, B, C, F>
+    where A: Send + Sync + Clone,
+          B: Send + Sync + Clone,
+          C: Send + Sync + Clone,
+          F: Fn(A, B) -> C + Send + Sync,
+{
+    fn listen(&mut self, a: A) {
+        let mut node = self.node.write().unwrap();
+        node.a = a;
+        node.recompute();
+    }
+}
+
+struct Lift2InputB<A, B, C, F> {
+    node: Arc<RwLock<Lift2<A, B, C, F>>>,
+}
+
+impl<A, B, C, F> Listener<B> for Lift2InputB<A, B, C, F>
+    where A: Send + Sync + Clone,
+          B: Send + Sync + Clone,
+          C: Send + Sync + Clone,
+          F: Fn(A, B) -> C + Send + Sync,
+{
+    fn listen(&mut self, b: B) {
+        let mut node = self.node.write().unwrap();
+        node.b = b;
+        node.recompute();
+    }
+}
+
+// Eager: recomputes f on every push from either side, not on sample.
+pub fn lift2<A, B, C, F>(a: &Signal<A>, b: &Signal<B>, f: F) -> Signal<C>
+    where A: Send + Sync + Clone + 'static,
+          B: Send + Sync + Clone + 'static,
+          C: Send + Sync + Clone,
+          F: Fn(A, B) -> C + Send + Sync + 'static,
+{
+    let initial = f(a.sample(), b.sample());
+    let node = Arc::new(RwLock::new(Lift2 {
+        a: a.sample(),
+        b: b.sample(),
+        f: f,
+        listeners: Vec::new(),
+    }));
+    a.holder.write().unwrap().listen(Lift2InputA { node: node.clone() });
+    b.holder.write().unwrap().listen(Lift2InputB { node: node.clone() });
+    Signal::new(&mut *node.write().unwrap(), initial)
+}
+
+
+struct MergeNode<A> {
+    resolver: Option<Box<Fn(A, A) -> A + Send + Sync>>,
+    listeners: Vec<Box<Listener<A>>>,
+}
+
+impl<A: Send + Sync + Clone> MergeNode<A> {
+    fn forward(&mut self, a: A) {
+        for listener in &mut self.listeners {
+            listener.listen(a.clone());
+        }
+    }
+}
+
+impl<A: Send + Sync + Clone> Subject<A> for MergeNode<A> {
+    fn listen<L: Listener<A> + Send + Sync + 'static>(&mut self, listener: L) {
+        self.listeners.push(Box::new(listener));
+    }
+}
+
+struct MergeInput<A> {
+    node: Arc<RwLock<MergeNode<A>>>,
+    pending: Arc<RwLock<Vec<A>>>,
+}
+
+impl<A> Clone for MergeInput<A> {
+    fn clone(&self) -> MergeInput<A> {
+        MergeInput { node: self.node.clone(), pending: self.pending.clone() }
+    }
+}
+
+impl<A: Send + Sync + Clone> Listener<A> for MergeInput<A> {
+    fn listen(&mut self, a: A) {
+        let mut node = match self.node.try_write() {
+            Ok(node) => node,
+            Err(_) => {
+                self.pending.write().unwrap().push(a);
+                return;
+            }
+        };
+        let mut value = a;
+        node.forward(value.clone());
+        loop {
+            let other = {
+                let mut pending = self.pending.write().unwrap();
+                if pending.is_empty() {
+                    break;
+                }
+                pending.remove(0)
+            };
+            value = match node.resolver {
+                Some(ref resolver) => resolver(value, other),
+                None => other,
+            };
+            node.forward(value.clone());
+        }
+    }
+}
+
+fn merge_node<A, E1, E2>(a: &E1, b: &E2, resolver: Option<Box<Fn(A, A) -> A + Send + Sync>>) -> Merge<A>
+    where A: Send + Sync + Clone + 'static,
+          E1: Listen<A>,
+          E2: Listen<A>,
+{
+    let merge = Merge {
+        node: Arc::new(RwLock::new(MergeNode { resolver: resolver, listeners: Vec::new() })),
+    };
+    let pending = Arc::new(RwLock::new(Vec::new()));
+    a.listen(MergeInput { node: merge.node.clone(), pending: pending.clone() });
+    b.listen(MergeInput { node: merge.node.clone(), pending: pending });
+    merge
+}
+
+
+pub struct Merge<A> {
+    node: Arc<RwLock<MergeNode<A>>>,
+}
+
+impl<A: Send + Sync + Clone> Event<A> for Merge<A> {
+    fn map<B, F>(&self, f: F) -> Map<A, B, F>
+        where B: Send + Sync + Clone,
+              F: Fn(A) -> B + Send + Sync,
+    {
+        Map::new(&mut *self.node.write().unwrap(), f)
+    }
+
+    fn filter<F>(&self, f: F) -> Filter<A, F>
+        where F: Fn(&A) -> bool + Send + Sync,
+    {
+        Filter::new(&mut *self.node.write().unwrap(), f)
+    }
+
+    fn iter(&self) -> Iter<A> {
+        Iter::new(&mut *self.node.write().unwrap())
+    }
+
+    fn hold(&self, initial: A) -> Signal<A> {
+        Signal::new(&mut *self.node.write().unwrap(), initial)
+    }
+
+    fn fold<B, F>(&self, init: B, f: F) -> Signal<B>
+        where B: Send + Sync + Clone,
+              F: Fn(&B, A) -> B + Send + Sync,
+    {
+        Signal::fold(&mut *self.node.write().unwrap(), init, f)
+    }
+}
+
+impl<A> sealed::Sealed for Merge<A> {}
+
+impl<A: Send + Sync + Clone> Listen<A> for Merge<A> {
+    fn listen<L: Listener<A> + Send + Sync + 'static>(&self, listener: L) {
+        self.node.write().unwrap().listen(listener);
+    }
+}
+
+pub fn merge<A, E1, E2>(a: &E1, b: &E2) -> Merge<A>
+    where A: Send + Sync + Clone + 'static,
+          E1: Event<A> + Listen<A>,
+          E2: Event<A> + Listen<A>,
+{
+    merge_node(a, b, None)
+}
+
+pub fn merge_with<A, E1, E2, F>(a: &E1, b: &E2, f: F) -> Merge<A>
+    where A: Send + Sync + Clone + 'static,
+          E1: Event<A> + Listen<A>,
+          E2: Event<A> + Listen<A>,
+          F: Fn(A, A) -> A + Send + Sync + 'static,
+{
+    merge_node(a, b, Some(Box::new(f)))
+}
+
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bytes(Vec<u8>),
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Timestamp(i64),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConvError(String);
+
+impl fmt::Display for ConvError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl ::std::error::Error for ConvError {
+    fn description(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Conversion {
+    Bytes,
+    String,
+    Integer,
+    Float,
+    Boolean,
+    Timestamp { format: String, timezone: Option<String> },
+}
+
+fn parse_offset(tz: &str) -> Option<chrono::FixedOffset> {
+    if tz == "UTC" || tz == "Z" {
+        return Some(chrono::FixedOffset::east(0));
+    }
+    let (sign, rest) = match tz.as_bytes().get(0) {
+        Some(b'+') => (1, &tz[1..]),
+        Some(b'-') => (-1, &tz[1..]),
+        _ => return None,
+    };
+    let rest = rest.replace(":", "");
+    if rest.len() != 4 || !rest.is_ascii() {
+        return None;
+    }
+    let hours: i32 = rest[..2].parse().ok()?;
+    let minutes: i32 = rest[2..].parse().ok()?;
+    Some(chrono::FixedOffset::east(sign * (hours * 3600 + minutes * 60)))
+}
+
+impl Conversion {
+    pub fn apply(&self, input: &str) -> Result<Value, ConvError> {
+        match *self {
+            Conversion::Bytes => Ok(Value::Bytes(input.as_bytes().to_vec())),
+            Conversion::String => Ok(Value::String(input.to_string())),
+            Conversion::Integer => {
+                input.parse().map(Value::Integer)
+                    .map_err(|e| ConvError(format!("invalid integer {:?}: {}", input, e)))
+            }
+            Conversion::Float => {
+                input.parse().map(Value::Float)
+                    .map_err(|e| ConvError(format!("invalid float {:?}: {}", input, e)))
+            }
+            Conversion::Boolean => {
+                input.parse().map(Value::Boolean)
+                    .map_err(|e| ConvError(format!("invalid boolean {:?}: {}", input, e)))
+            }
+            Conversion::Timestamp { ref format, ref timezone } => {
+                use self::chrono::TimeZone;
+
+                let naive = chrono::NaiveDateTime::parse_from_str(input, format)
+                    .map_err(|e| ConvError(format!("invalid timestamp {:?} for format {:?}: {}",
+                                                    input, format, e)))?;
+                let offset = match *timezone {
+                    Some(ref tz) => parse_offset(tz)
+                        .ok_or_else(|| ConvError(format!("unsupported timezone {:?}", tz)))?,
+                    None => chrono::FixedOffset::east(0),
+                };
+                let timestamp = offset.from_local_datetime(&naive)
+                    .single()
+                    .ok_or_else(|| ConvError(format!("ambiguous local time {:?}", input)))?
+                    .timestamp();
+                Ok(Value::Timestamp(timestamp))
+            }
+        }
+    }
+}
+
+impl FromStr for Conversion {
+    type Err = ConvError;
+
+    fn from_str(s: &str) -> Result<Conversion, ConvError> {
+        let mut parts = s.splitn(3, '|');
+        match parts.next().unwrap_or("") {
+            "bytes" => Ok(Conversion::Bytes),
+            "string" => Ok(Conversion::String),
+            "int" | "integer" => Ok(Conversion::Integer),
+            "float" => Ok(Conversion::Float),
+            "bool" | "boolean" => Ok(Conversion::Boolean),
+            "timestamp" => {
+                let format = parts.next()
+                    .ok_or_else(|| ConvError(format!("missing timestamp format in {:?}", s)))?;
+                Ok(Conversion::Timestamp {
+                    format: format.to_string(),
+                    timezone: parts.next().map(|tz| tz.to_string()),
+                })
+            }
+            other => Err(ConvError(format!("unknown conversion {:?}", other))),
+        }
+    }
+}
+
+pub fn try_map(event: &impl Event<String>, conv: Conversion) -> impl Event<Result<Value, ConvError>> {
+    event.map(move |s: String| conv.apply(&s))
+}
+
+pub fn convert(event: &impl Event<String>, conv: Conversion) -> impl Event<Value> {
+    try_map(event, conv).filter(|r| r.is_ok()).map(|r| r.unwrap())
+}
+
 
 #[cfg(test)]
 mod test {
@@ -179,4 +794,184 @@ mod test {
         sink.send(9);
         assert_eq!(iter.next(), Some(9));
     }
+
+    #[test]
+    fn hold() {
+        let sink = Sink::new();
+        let signal = sink.hold(0);
+        assert_eq!(signal.sample(), 0);
+        sink.send(3);
+        assert_eq!(signal.sample(), 3);
+    }
+
+    #[test]
+    fn snapshot() {
+        let numbers = Sink::new();
+        let letters = Sink::new();
+        let signal = numbers.hold(0);
+        let mut snapshots = signal.snapshot(&letters).iter();
+        numbers.send(1);
+        letters.send('a');
+        assert_eq!(snapshots.next(), Some((1, 'a')));
+    }
+
+    #[test]
+    fn signal_map() {
+        let sink = Sink::new();
+        let doubled = sink.hold(1).map(|x| 2 * x);
+        assert_eq!(doubled.sample(), 2);
+        sink.send(4);
+        assert_eq!(doubled.sample(), 8);
+    }
+
+    #[test]
+    fn fold() {
+        let sink = Sink::new();
+        let sum = sink.fold(0, |total, x| total + x);
+        assert_eq!(sum.sample(), 0);
+        sink.send(2);
+        sink.send(5);
+        assert_eq!(sum.sample(), 7);
+    }
+
+    #[test]
+    fn fold_as_event() {
+        let sink = Sink::new();
+        let sum = sink.fold(0, |total, x| total + x);
+        let mut running = sum.iter();
+        sink.send(2);
+        sink.send(5);
+        assert_eq!(running.next(), Some(2));
+        assert_eq!(running.next(), Some(7));
+        assert_eq!(sum.sample(), 7);
+    }
+
+    #[test]
+    fn conversion_from_str() {
+        assert_eq!("int".parse(), Ok(Conversion::Integer));
+        assert_eq!("timestamp|%Y-%m-%d|UTC".parse(), Ok(Conversion::Timestamp {
+            format: "%Y-%m-%d".to_string(),
+            timezone: Some("UTC".to_string()),
+        }));
+        assert!("nonsense".parse::<Conversion>().is_err());
+    }
+
+    #[test]
+    fn conversion_apply_timestamp() {
+        let conv: Conversion = "timestamp|%Y-%m-%d %H:%M:%S|UTC".parse().unwrap();
+        assert_eq!(conv.apply("2024-01-02 03:04:05"), Ok(Value::Timestamp(1704164645)));
+        assert!(conv.apply("not a timestamp").is_err());
+    }
+
+    #[test]
+    fn conversion_apply_timestamp_rejects_non_ascii_offset() {
+        let conv: Conversion = "timestamp|%Y-%m-%d %H:%M:%S|+0\u{6c34}".parse().unwrap();
+        assert!(conv.apply("2024-01-02 03:04:05").is_err());
+    }
+
+    #[test]
+    fn convert_drops_failures() {
+        let sink: Sink<String> = Sink::new();
+        let mut integers = convert(&sink, Conversion::Integer).iter();
+        sink.send("not a number".to_string());
+        sink.send("42".to_string());
+        assert_eq!(integers.next(), Some(Value::Integer(42)));
+    }
+
+    #[test]
+    fn try_map_forwards_errors() {
+        let sink: Sink<String> = Sink::new();
+        let mut results = try_map(&sink, Conversion::Integer).iter();
+        sink.send("nope".to_string());
+        assert!(results.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn into_stream() {
+        use futures::{Async, Future, Stream};
+        use futures::future::lazy;
+
+        // poll() parks the current task when empty, so it must run inside one.
+        lazy(|| {
+            let sink = Sink::new();
+            let mut stream = sink.iter().into_stream();
+            assert_eq!(stream.poll(), Ok(Async::NotReady));
+            sink.send(1);
+            assert_eq!(stream.poll(), Ok(Async::Ready(Some(1))));
+            Ok::<(), ()>(())
+        }).wait().unwrap();
+    }
+
+    #[test]
+    fn into_stream_wakes_parked_task() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use futures::Async;
+        use futures::executor::{self, Notify, NotifyHandle};
+
+        struct Flag(AtomicBool);
+
+        impl Notify for Flag {
+            fn notify(&self, _id: usize) {
+                self.0.store(true, Ordering::SeqCst);
+            }
+        }
+
+        let sink = Sink::new();
+        let mut spawn = executor::spawn(sink.iter().into_stream());
+        let flag = Arc::new(Flag(AtomicBool::new(false)));
+        let handle = NotifyHandle::from(flag.clone());
+
+        assert_eq!(spawn.poll_stream_notify(&handle, 0), Ok(Async::NotReady));
+        assert!(!flag.0.load(Ordering::SeqCst));
+
+        sink.send(1);
+        assert!(flag.0.load(Ordering::SeqCst));
+        assert_eq!(spawn.poll_stream_notify(&handle, 0), Ok(Async::Ready(Some(1))));
+    }
+
+    #[test]
+    fn merge() {
+        let a = Sink::new();
+        let b = Sink::new();
+        let mut merged = merge(&a, &b).iter();
+        a.send(1);
+        b.send(2);
+        a.send(3);
+        assert_eq!(merged.next(), Some(1));
+        assert_eq!(merged.next(), Some(2));
+        assert_eq!(merged.next(), Some(3));
+    }
+
+    #[test]
+    fn merge_with() {
+        let a = Sink::new();
+        let b = Sink::new();
+        let mut merged = merge_with(&a, &b, |x, y| x + y).iter();
+        a.send(1);
+        b.send(2);
+        assert_eq!(merged.next(), Some(1));
+        assert_eq!(merged.next(), Some(2));
+    }
+
+    #[test]
+    fn merge_with_same_step() {
+        let a = Sink::new();
+        let b = Sink::new();
+        let merged = merge_with(&a, &b, |x, y| x + y);
+        let mut iter = merged.iter();
+        let _bridge = merged.filter(|&x| x == 1).map(move |x| { b.send(2); x });
+        a.send(1);
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.next(), Some(3));
+    }
+
+    #[test]
+    fn signal_lift2() {
+        let a = Sink::new();
+        let b = Sink::new();
+        let sum = lift2(&a.hold(1), &b.hold(2), |x, y| x + y);
+        assert_eq!(sum.sample(), 3);
+        a.send(10);
+        assert_eq!(sum.sample(), 12);
+    }
 }
\ No newline at end of file